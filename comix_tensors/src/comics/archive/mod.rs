@@ -0,0 +1,91 @@
+mod seven_zip;
+mod tar;
+
+#[cfg(test)]
+pub(crate) mod tests;
+
+use std::error::Error;
+use std::fmt;
+
+use tokio::prelude::*;
+
+pub use seven_zip::SevenZipArchive;
+pub use tar::TarArchive;
+
+///Single file or directory entry extracted from a comic book archive
+#[derive(Debug, Clone)]
+pub struct ArchiveFile {
+    ///Position of the entry inside the archive
+    pub pos: usize,
+    ///Name of the entry
+    pub name: String,
+    ///Is the entry a directory
+    pub is_dir: bool,
+    ///Raw content of the entry. `None` for directories
+    pub content: Option<Vec<u8>>,
+    ///Uncompressed size of the entry in bytes
+    pub size: u64,
+    ///Modification time, if the archive format/entry carries one
+    pub mtime: Option<u64>,
+    ///Windows/Unix attribute flags, if the archive format/entry carries any
+    pub attributes: Option<u32>,
+}
+
+///Stream of [ArchiveFile] produced by a [ComicContainer]
+pub type ComicFilesStream = Box<dyn Stream<Item = ArchiveFile, Error = ComicArchiveError> + Send>;
+
+///Abstraction over comic book archive formats (7z, tar, zip, etc.)
+pub trait ComicContainer {
+    ///Stream over all files in the archive
+    fn files(&self) -> ComicFilesStream;
+}
+
+///Metadata describing a single archive entry, obtainable without decompressing its content
+#[derive(Debug, Clone)]
+pub struct ArchiveFileMeta {
+    ///Position of the entry inside the archive
+    pub pos: usize,
+    ///Name of the entry
+    pub name: String,
+    ///Is the entry a directory
+    pub is_dir: bool,
+    ///Uncompressed size of the entry in bytes
+    pub size: u64,
+    ///Modification time, if the archive format/entry carries one
+    pub mtime: Option<u64>,
+    ///Windows/Unix attribute flags, if the archive format/entry carries any
+    pub attributes: Option<u32>,
+}
+
+///A [ComicContainer] variant for formats that support cheap listing and on-demand single entry
+///extraction, instead of eagerly streaming (and decompressing) every entry up front
+pub trait LazyComicContainer {
+    type Error: Error;
+
+    ///Cheaply list every entry in the archive, without decompressing any content
+    fn list_entries(&self) -> Result<Vec<ArchiveFileMeta>, Self::Error>;
+
+    ///Extract a single entry by its position, decompressing only what's required for it
+    fn read_entry(&self, pos: usize) -> Result<ArchiveFile, Self::Error>;
+}
+
+///Generic error returned by any [ComicContainer] implementation
+#[derive(Debug)]
+pub struct ComicArchiveError(Box<dyn Error + Send + Sync>);
+
+impl fmt::Display for ComicArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ComicArchiveError {}
+
+impl<E> From<E> for ComicArchiveError
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn from(e: E) -> Self {
+        ComicArchiveError(Box::new(e))
+    }
+}
@@ -0,0 +1,70 @@
+use std::error::Error;
+use std::fmt;
+use std::string::FromUtf16Error;
+
+use lzma_sdk_sys::SZ;
+
+pub type SevenZipResult<T> = Result<T, SevenZipError>;
+
+///Errors which can occur while working with a 7z archive
+#[derive(Debug)]
+pub enum SevenZipError {
+    ///Underlying LZMA SDK call returned a non `SZ_OK` result
+    Native(SZ),
+    ///File name stored in the archive isn't a valid UTF-16 string
+    InvalidName(FromUtf16Error),
+    ///The archive's header or content is AES-256 encrypted and no password was provided
+    PasswordRequired,
+    ///The provided password didn't decrypt the archive's header or content
+    WrongPassword,
+    ///Recomputed CRC32 of an extracted entry didn't match the checksum stored in the archive
+    CrcMismatch {
+        pos: u32,
+        expected: u32,
+        actual: u32,
+    },
+    ///Requested entry position is outside the archive's entry count
+    InvalidPosition { pos: u32, count: usize },
+    ///Generic error message, used for conditions not covered by a native result code
+    Msg(String),
+}
+
+impl fmt::Display for SevenZipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SevenZipError::Native(res) => write!(f, "7z native error: {:?}", res),
+            SevenZipError::InvalidName(e) => write!(f, "7z invalid entry name: {}", e),
+            SevenZipError::PasswordRequired => write!(f, "7z archive is password protected"),
+            SevenZipError::WrongPassword => write!(f, "7z archive password is incorrect"),
+            SevenZipError::CrcMismatch {
+                pos,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "7z entry at position {} is corrupted: expected CRC32 {:08x}, got {:08x}",
+                pos, expected, actual
+            ),
+            SevenZipError::InvalidPosition { pos, count } => write!(
+                f,
+                "7z entry position {} is out of range, archive has {} entries",
+                pos, count
+            ),
+            SevenZipError::Msg(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for SevenZipError {}
+
+impl From<String> for SevenZipError {
+    fn from(msg: String) -> Self {
+        SevenZipError::Msg(msg)
+    }
+}
+
+impl From<FromUtf16Error> for SevenZipError {
+    fn from(e: FromUtf16Error) -> Self {
+        SevenZipError::InvalidName(e)
+    }
+}
@@ -1,13 +1,15 @@
 mod error;
 
-use self::error::*;
-use super::{ArchiveFile, ComicContainer, ComicFilesStream};
+use self::error::{SevenZipError, SevenZipResult};
+use super::{ArchiveFile, ArchiveFileMeta, ComicContainer, ComicFilesStream};
 
 use std::ffi::CString;
+use std::iter;
 use std::mem;
 use std::os::unix::io::RawFd;
 use std::ptr;
 use std::slice;
+use std::sync::Mutex;
 use std::thread::current as current_thread;
 
 use libc::{dup, fclose, fdopen};
@@ -19,8 +21,6 @@ use tokio::prelude::*;
 
 const INPUT_BUF_SIZE: size_t = (1 << 18);
 
-type SevenZipResult<T> = Result<T, SevenZipError>;
-
 ///Open archive from file descriptor
 fn open_archive(fd: RawFd) -> SevenZipResult<lzma::CFileInStream> {
     debug!("Trying to open 7z archive using FD: {}", fd);
@@ -40,25 +40,52 @@ fn open_archive(fd: RawFd) -> SevenZipResult<lzma::CFileInStream> {
 }
 
 ///Start point of the 7z archive logic
-#[derive(Debug, Copy, Clone)]
-pub struct SevenZipArchive(RawFd);
+#[derive(Debug, Clone)]
+pub struct SevenZipArchive {
+    fd: RawFd,
+    password: Option<String>,
+    verify_crc: bool,
+}
 
 impl SevenZipArchive {
     pub fn new(fd: RawFd) -> Self {
         debug!("File descriptor of the 7z archive: {}", fd);
-        SevenZipArchive(fd)
+        SevenZipArchive {
+            fd,
+            password: None,
+            verify_crc: false,
+        }
+    }
+
+    ///Provide a password, used to decrypt the AES-256 encrypted content of 7z archive entries.
+    ///The reader UI can call this and retry after a [SevenZipError::PasswordRequired] or
+    ///[SevenZipError::WrongPassword]. The password isn't used while opening the archive, so an
+    ///archive whose header is encrypted can't be opened with any password
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    ///Enable CRC32 verification of every extracted entry against the checksum stored in the
+    ///archive, returning [SevenZipError::CrcMismatch] instead of silently handing back corrupt data
+    pub fn with_crc_verification(mut self, verify: bool) -> Self {
+        self.verify_crc = verify;
+        self
     }
 
     ///Open archive in the [Future]
     fn open(&self) -> impl Future<Item = OpenedArchive, Error = SevenZipError> {
-        let fd = self.0;
+        let fd = self.fd;
+        let password = self.password.clone();
+        let verify_crc = self.verify_crc;
 
         future::lazy(move || {
             let fd = unsafe { dup(fd) };
 
             debug!("Trying to init 7z ArchiveData");
             let mut archive_data = ArchiveData::new(fd)?;
-            archive_data.init(INPUT_BUF_SIZE)?;
+            archive_data.init(INPUT_BUF_SIZE, password.as_deref())?;
+            archive_data.verify_crc = verify_crc;
             debug!("7z ArchiveData inited");
 
             Ok(OpenedArchive::from(archive_data))
@@ -77,6 +104,41 @@ impl ComicContainer for SevenZipArchive {
     }
 }
 
+impl SevenZipArchive {
+    ///Open the archive once and keep the handle alive for repeated random access, so the
+    ///solid-block cache (`out_buffer`/`block_index`) survives across calls. This is the only lazy
+    ///(list/extract-on-demand) entry point: a `&self`-based [LazyComicContainer] impl here would
+    ///have to reopen (and fully re-parse the header of) a fresh archive on every single call, which
+    ///defeats the whole point of caching for a "show page 1, then page 2, then page 3…" navigation
+    ///pattern — so that trait isn't implemented for this type at all
+    pub fn open_lazy(&self) -> SevenZipResult<LazyArchive> {
+        Ok(LazyArchive(Mutex::new(self.open().wait()?)))
+    }
+}
+
+///A single opened 7z archive handle kept alive across calls, so its solid-block cache
+///(`out_buffer`/`block_index`) is reused instead of being rebuilt on every call. Obtained via
+///[SevenZipArchive::open_lazy]
+pub struct LazyArchive(Mutex<OpenedArchive>);
+
+impl LazyArchive {
+    ///Cheaply list every entry (name, size, ...) without decompressing any content
+    pub fn list_entries(&self) -> SevenZipResult<Vec<ArchiveFileMeta>> {
+        self.0.lock().unwrap().list_entries()
+    }
+
+    ///Extract a single entry by position, reusing the cached solid block from any previous call
+    ///against this same handle
+    pub fn read_entry(&self, pos: u32) -> SevenZipResult<ArchiveFile> {
+        self.0.lock().unwrap().read_entry(pos)
+    }
+
+    ///Extract several entries at once, decompressing each solid block they belong to only once
+    pub fn read_entries(&self, positions: &[u32]) -> SevenZipResult<Vec<ArchiveFile>> {
+        self.0.lock().unwrap().read_entries(positions)
+    }
+}
+
 ///Data associated with [OpenedArchive]
 #[derive(Debug, Clone)]
 struct ArchiveData {
@@ -87,6 +149,11 @@ struct ArchiveData {
     alloc_temp_imp: lzma::ISzAlloc,
     look_stream: lzma::CLookToRead2,
     db: lzma::CSzArEx,
+    //NUL-terminated UTF-16 password, fed to the AES coder at folder-decode time (see `read_file`);
+    //the plain C SDK has no mechanism to decrypt an AES-encrypted *header* with it, so an encrypted
+    //header always surfaces as `PasswordRequired`/`WrongPassword` regardless of this field
+    password: Option<Vec<UInt16>>,
+    verify_crc: bool,
 }
 
 impl ArchiveData {
@@ -98,11 +165,15 @@ impl ArchiveData {
             alloc_temp_imp: lzma::ISzAlloc::g_alloc(),
             look_stream: lzma::CLookToRead2::default(),
             db: lzma::CSzArEx::default(),
+            password: None,
+            verify_crc: false,
         })
     }
 
-    ///Init data
-    fn init(&mut self, buf_size: size_t) -> SevenZipResult<()> {
+    ///Init data. [password], if provided, is converted to the UTF-16 representation the LZMA SDK's
+    ///AES-256 coder expects and kept around for `read_file` to pass into `SzArEx_Extract`, which is
+    ///where folder decode (and so AES decryption) actually happens
+    fn init(&mut self, buf_size: size_t, password: Option<&str>) -> SevenZipResult<()> {
         unsafe {
             lzma::FileInStream_CreateVTable(&mut *self.archive_stream);
             lzma::LookToRead2_CreateVTable(&mut self.look_stream, 0);
@@ -120,6 +191,8 @@ impl ArchiveData {
         self.look_stream.realStream = &self.archive_stream.vt;
         self.look_stream.init();
 
+        self.password = password.map(password_to_utf16);
+
         {
             let res = unsafe {
                 lzma::CrcGenerateTable();
@@ -134,8 +207,20 @@ impl ArchiveData {
             };
 
             if !res.is_ok() {
-                error!("7z archive can't open extractor. Result: {:?}", res);
-                return Err(SevenZipError::Native(res));
+                return Err(match res {
+                    SZ::SZ_ERROR_CRC if self.password.is_none() => {
+                        debug!("7z archive header can't be decoded, assuming it's encrypted");
+                        SevenZipError::PasswordRequired
+                    }
+                    SZ::SZ_ERROR_CRC => {
+                        debug!("7z archive header can't be decoded with the provided password");
+                        SevenZipError::WrongPassword
+                    }
+                    _ => {
+                        error!("7z archive can't open extractor. Result: {:?}", res);
+                        SevenZipError::Native(res)
+                    }
+                });
             }
         }
 
@@ -143,6 +228,78 @@ impl ArchiveData {
     }
 }
 
+///Convert an UTF-8 password into the NUL-terminated UTF-16 representation expected by the LZMA
+///SDK's AES-256 coder setup
+fn password_to_utf16(password: &str) -> Vec<UInt16> {
+    password.encode_utf16().chain(iter::once(0)).collect()
+}
+
+///`Defs` on `CSzBitUi32s`/`CSzBitUi64s` is a bit-packed MSB-0 bitmap, not one byte per entry: bit
+///`pos` lives in byte `pos >> 3`, and the whole bitmap is only `ceil(num_files / 8)` bytes long
+fn is_def_bit_set(defs: *const Byte, num_files: usize, pos: UInt32) -> bool {
+    //a NULL bitmap means the property isn't defined for any entry
+    if defs.is_null() {
+        return false;
+    }
+
+    let defs = unsafe { slice::from_raw_parts(defs, (num_files + 7) / 8) };
+
+    defs[pos as usize >> 3] & (0x80 >> (pos & 7)) != 0
+}
+
+///Look up the CRC32 stored for entry [pos] in the archive's database, guarded by the CRC-defined
+///bit-vector since directories and some entries carry no CRC
+fn stored_crc(db: &lzma::CSzArEx, pos: UInt32) -> Option<UInt32> {
+    let num_files = db.NumFiles as usize;
+
+    if !is_def_bit_set(db.CRCs.Defs, num_files, pos) {
+        return None;
+    }
+
+    let vals = unsafe { slice::from_raw_parts(db.CRCs.Vals, num_files) };
+
+    Some(vals[pos as usize])
+}
+
+///Uncompressed size of entry [pos], read from the database's `UnpackPositions` table without
+///decompressing anything
+fn entry_size(db: &lzma::CSzArEx, pos: UInt32) -> u64 {
+    unsafe {
+        let positions = slice::from_raw_parts(db.UnpackPositions, db.NumFiles as usize + 1);
+
+        positions[pos as usize + 1] - positions[pos as usize]
+    }
+}
+
+///Look up entry [pos]'s modification time, guarded by the `MTime` bit-vector since not every
+///entry carries one
+fn entry_mtime(db: &lzma::CSzArEx, pos: UInt32) -> Option<u64> {
+    let num_files = db.NumFiles as usize;
+
+    if !is_def_bit_set(db.MTime.Defs, num_files, pos) {
+        return None;
+    }
+
+    let vals = unsafe { slice::from_raw_parts(db.MTime.Vals, num_files) };
+    let mtime = vals[pos as usize];
+
+    Some((mtime.High as u64) << 32 | mtime.Low as u64)
+}
+
+///Look up entry [pos]'s Windows/Unix attribute flags, guarded by the `Attribs` bit-vector since
+///not every entry carries any
+fn entry_attributes(db: &lzma::CSzArEx, pos: UInt32) -> Option<u32> {
+    let num_files = db.NumFiles as usize;
+
+    if !is_def_bit_set(db.Attribs.Defs, num_files, pos) {
+        return None;
+    }
+
+    let vals = unsafe { slice::from_raw_parts(db.Attribs.Vals, num_files) };
+
+    Some(vals[pos as usize])
+}
+
 impl Drop for ArchiveData {
     fn drop(&mut self) {
         unsafe {
@@ -208,21 +365,26 @@ impl OpenedArchive {
     fn files_count(&self) -> usize {
         self.archive_data.db.NumFiles as usize
     }
-}
-
-///Iterator over files in the archive
-struct ArchiveIterator {
-    archive: OpenedArchive,
-    current_pos: usize,
-}
 
-impl ArchiveIterator {
     ///Check is file by [pos] directory or not
     fn is_dir(&self, pos: UInt32) -> bool {
-        self.archive.archive_data.db.is_dir(pos)
+        self.archive_data.db.is_dir(pos)
+    }
+
+    ///Reject a [pos] that's outside the archive's entry count before it reaches any unchecked
+    ///raw-pointer lookup or native `SzArEx_*` call, both of which trust it blindly
+    fn check_pos(&self, pos: UInt32) -> SevenZipResult<()> {
+        let count = self.files_count();
+
+        if pos as usize >= count {
+            return Err(SevenZipError::InvalidPosition { pos, count });
+        }
+
+        Ok(())
     }
 
-    ///Read and return file content by position [pos]
+    ///Read and return file content by position [pos]. Only the solid block containing [pos] is
+    ///decompressed; if it was already decompressed by a previous call its cached `out_buffer` is reused
     fn read_file<'a>(&mut self, pos: UInt32) -> SevenZipResult<&'a mut [u8]> {
         debug!(
             "Trying to get 7z file content by position: {}. Thread: {:?}",
@@ -233,38 +395,67 @@ impl ArchiveIterator {
         let mut offset = 0 as size_t;
         let mut out_size_processed = 0 as size_t;
 
+        //the password is threaded in here, at folder-decode time, since that's where the AES-256
+        //coder actually runs
         let res = unsafe {
             lzma::SzArEx_Extract(
-                &self.archive.archive_data.db,
-                &mut self.archive.archive_data.look_stream.vt,
+                &self.archive_data.db,
+                &mut self.archive_data.look_stream.vt,
                 pos,
-                &mut self.archive.block_index,
-                &mut self.archive.out_buffer,
-                &mut self.archive.out_buffer_size,
+                &mut self.block_index,
+                &mut self.out_buffer,
+                &mut self.out_buffer_size,
                 &mut offset,
                 &mut out_size_processed,
-                &mut self.archive.archive_data.alloc_imp,
-                &mut self.archive.archive_data.alloc_temp_imp,
+                self.archive_data
+                    .password
+                    .as_ref()
+                    .map_or(ptr::null(), |pw| pw.as_ptr()),
+                &mut self.archive_data.alloc_imp,
+                &mut self.archive_data.alloc_temp_imp,
             )
         };
 
         if !res.is_ok() {
-            let txt = format!(
-                "Can't get 7z file content by position {}. Result {:?}",
-                pos, res
-            );
+            return Err(match res {
+                SZ::SZ_ERROR_CRC if self.archive_data.password.is_none() => {
+                    SevenZipError::PasswordRequired
+                }
+                SZ::SZ_ERROR_CRC => SevenZipError::WrongPassword,
+                _ => {
+                    let txt = format!(
+                        "Can't get 7z file content by position {}. Result {:?}",
+                        pos, res
+                    );
 
-            error!("{}", txt);
-            return Err(txt.into());
+                    error!("{}", txt);
+                    txt.into()
+                }
+            });
         }
 
-        Ok(unsafe {
-            slice::from_raw_parts_mut(self.archive.out_buffer.add(offset), out_size_processed)
-        })
+        let content =
+            unsafe { slice::from_raw_parts_mut(self.out_buffer.add(offset), out_size_processed) };
+
+        if self.archive_data.verify_crc {
+            if let Some(expected) = stored_crc(&self.archive_data.db, pos) {
+                let actual = unsafe { lzma::CrcCalc(content.as_ptr() as *const _, content.len()) };
+
+                if actual != expected {
+                    return Err(SevenZipError::CrcMismatch {
+                        pos,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Ok(content)
     }
 
-    ///Read and return file name by position [pos]
-    pub fn read_file_name(&mut self, pos: size_t) -> SevenZipResult<String> {
+    ///Read and return file name by position [pos], using the cached name buffer
+    fn read_file_name(&mut self, pos: size_t) -> SevenZipResult<String> {
         debug!(
             "Trying to get 7z file name by position: {}. Thread: {:?}",
             pos,
@@ -272,22 +463,21 @@ impl ArchiveIterator {
         );
 
         let file_name = unsafe {
-            let len =
-                lzma::SzArEx_GetFileNameUtf16(&self.archive.archive_data.db, pos, ptr::null_mut());
+            let len = lzma::SzArEx_GetFileNameUtf16(&self.archive_data.db, pos, ptr::null_mut());
 
             //If there is no enough space in the buffer. Allocate a new one with proper size
-            if len > self.archive.temp_size {
-                lzma::SzFree(ptr::null_mut(), self.archive.temp as *mut _);
+            if len > self.temp_size {
+                lzma::SzFree(ptr::null_mut(), self.temp as *mut _);
 
-                self.archive.temp_size = len;
+                self.temp_size = len;
 
-                self.archive.temp = lzma::SzAlloc(
+                self.temp = lzma::SzAlloc(
                     ptr::null_mut(),
-                    self.archive.temp_size * mem::size_of_val(&self.archive.temp),
+                    self.temp_size * mem::size_of_val(&self.temp),
                 ) as *mut _;
 
                 //temp = (UInt16 *)SzAlloc(NULL, tempSize * sizeof(temp[0]));
-                if self.archive.temp.is_null() {
+                if self.temp.is_null() {
                     let txt = format!("Can't get 7z file name by position {}", pos);
 
                     error!("{}", txt);
@@ -295,13 +485,107 @@ impl ArchiveIterator {
                 }
             }
 
-            lzma::SzArEx_GetFileNameUtf16(&self.archive.archive_data.db, pos, self.archive.temp);
+            lzma::SzArEx_GetFileNameUtf16(&self.archive_data.db, pos, self.temp);
 
-            slice::from_raw_parts(self.archive.temp, len - 1)
+            slice::from_raw_parts(self.temp, len - 1)
         };
 
         Ok(String::from_utf16(file_name)?)
     }
+
+    ///Read a file name by position without touching the cached name buffer, so it can be called
+    ///through a shared reference while cheaply listing entries
+    fn file_name(&self, pos: size_t) -> SevenZipResult<String> {
+        let len = unsafe { lzma::SzArEx_GetFileNameUtf16(&self.archive_data.db, pos, ptr::null_mut()) };
+
+        let mut buf = vec![0 as UInt16; len];
+
+        unsafe { lzma::SzArEx_GetFileNameUtf16(&self.archive_data.db, pos, buf.as_mut_ptr()) };
+
+        //drop the trailing NUL terminator returned by SzArEx_GetFileNameUtf16
+        buf.truncate(len.saturating_sub(1));
+
+        Ok(String::from_utf16(&buf)?)
+    }
+
+    ///Cheaply enumerate every entry in the archive (name, size, mtime, attributes, no decompression)
+    pub fn list_entries(&self) -> SevenZipResult<Vec<ArchiveFileMeta>> {
+        let db = &self.archive_data.db;
+
+        (0..self.files_count())
+            .map(|pos| {
+                Ok(ArchiveFileMeta {
+                    pos,
+                    name: self.file_name(pos)?,
+                    is_dir: self.is_dir(pos as _),
+                    size: entry_size(db, pos as _),
+                    mtime: entry_mtime(db, pos as _),
+                    attributes: entry_attributes(db, pos as _),
+                })
+            })
+            .collect()
+    }
+
+    ///Extract a single entry by position on demand, without walking the preceding entries first.
+    ///[pos] is untrusted input (a page-jump/prefetch request crossing the JNI boundary), so it's
+    ///checked against the entry count before being handed to any of the unchecked raw-pointer
+    ///lookups below or to the native `SzArEx_Extract` call
+    pub fn read_entry(&mut self, pos: u32) -> SevenZipResult<ArchiveFile> {
+        self.check_pos(pos)?;
+
+        let is_dir = self.is_dir(pos);
+        let name = self.read_file_name(pos as _)?;
+        let size = entry_size(&self.archive_data.db, pos);
+        let mtime = entry_mtime(&self.archive_data.db, pos);
+        let attributes = entry_attributes(&self.archive_data.db, pos);
+
+        let content = if is_dir {
+            None
+        } else {
+            Some(self.read_file(pos)?.to_owned())
+        };
+
+        Ok(ArchiveFile {
+            pos: pos as usize,
+            name,
+            is_dir,
+            content,
+            size,
+            mtime,
+            attributes,
+        })
+    }
+
+    ///Extract several entries in one pass, decompressing each solid block ("folder") it owns at
+    ///most once regardless of how many requested [positions] fall inside it. Positions are grouped
+    ///by their owning folder first, so a prefetch of N pages from the same folder costs one
+    ///decompress instead of N
+    pub fn read_entries(&mut self, positions: &[u32]) -> SevenZipResult<Vec<ArchiveFile>> {
+        let mut by_folder: Vec<(UInt32, Vec<u32>)> = Vec::new();
+
+        for &pos in positions {
+            self.check_pos(pos)?;
+
+            let folder = unsafe { lzma::SzArEx_GetFolderIndex(&self.archive_data.db, pos) };
+
+            match by_folder.iter_mut().find(|(f, _)| *f == folder) {
+                Some((_, group)) => group.push(pos),
+                None => by_folder.push((folder, vec![pos])),
+            }
+        }
+
+        by_folder
+            .into_iter()
+            .flat_map(|(_, group)| group)
+            .map(|pos| self.read_entry(pos))
+            .collect()
+    }
+}
+
+///Iterator over files in the archive
+struct ArchiveIterator {
+    archive: OpenedArchive,
+    current_pos: usize,
 }
 
 impl Iterator for ArchiveIterator {
@@ -312,24 +596,9 @@ impl Iterator for ArchiveIterator {
             return None;
         }
 
-        let is_dir = self.is_dir(self.current_pos as _);
-
-        let file_name = match self.read_file_name(self.current_pos) {
+        let file = match self.archive.read_entry(self.current_pos as _) {
             Err(e) => return Some(Err(e)),
-            Ok(file_name) => file_name,
-        };
-
-        let file_content = match self.read_file(self.current_pos as _).map(|c| c.to_owned()) {
-            Err(e) => return Some(Err(e)),
-            Ok(_) if is_dir => None,
-            Ok(content) => Some(content),
-        };
-
-        let file = ArchiveFile {
-            pos: self.current_pos,
-            name: file_name,
-            is_dir,
-            content: file_content,
+            Ok(file) => file,
         };
 
         debug!(
@@ -405,6 +674,115 @@ mod tests {
         assert_eq!(res, MagicType::SZ);
     }
 
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_crc_verification_accepts_untampered_content() {
+        let fd = open_7z_fd();
+        let archive = SevenZipArchive::new(fd).with_crc_verification(true).open_lazy().unwrap();
+
+        let positions: Vec<u32> = archive
+            .list_entries()
+            .unwrap()
+            .into_iter()
+            .filter(|e| !e.is_dir)
+            .map(|e| e.pos as u32)
+            .collect();
+
+        assert!(!positions.is_empty(), "fixture should contain at least one file");
+
+        for pos in positions {
+            archive
+                .read_entry(pos)
+                .unwrap_or_else(|e| panic!("CRC verification should pass for entry {}: {}", pos, e));
+        }
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_list_entries_exposes_size_mtime_and_attributes() {
+        let fd = open_7z_fd();
+        let entries = SevenZipArchive::new(fd).open_lazy().unwrap().list_entries().unwrap();
+
+        assert_eq!(entries.len(), 11, "Wrong number of 7z archive entries");
+
+        let file = entries
+            .iter()
+            .find(|e| !e.is_dir)
+            .expect("fixture should contain at least one file");
+
+        assert!(file.size > 0, "a file entry should report a non-zero uncompressed size");
+        assert!(file.mtime.is_some(), "a file entry should carry a modification time");
+        assert!(file.attributes.is_some(), "a file entry should carry attribute flags");
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_read_entries_matches_individual_read_entry() {
+        let fd = open_7z_fd();
+        let archive = SevenZipArchive::new(fd).open_lazy().unwrap();
+
+        let count = archive.list_entries().unwrap().len();
+        let positions: Vec<u32> = (0..count as u32).collect();
+
+        let batch = archive.read_entries(&positions).unwrap();
+        assert_eq!(batch.len(), positions.len());
+
+        for &pos in &positions {
+            let individual = archive.read_entry(pos).unwrap();
+            let from_batch = batch
+                .iter()
+                .find(|f| f.pos == pos as usize)
+                .unwrap_or_else(|| panic!("read_entries should return an entry for position {}", pos));
+
+            assert_eq!(from_batch.name, individual.name);
+            assert_eq!(from_batch.content, individual.content);
+        }
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_open_encrypted_archive_requires_correct_password() {
+        const PASSWORD: &str = "comics";
+
+        let fd = open_archive_fd(&["7z", "comics_test_encrypted.cb7"]);
+        let err = SevenZipArchive::new(fd).open_lazy().unwrap_err();
+        assert!(
+            matches!(err, SevenZipError::PasswordRequired),
+            "opening an encrypted archive without a password should report PasswordRequired, got {:?}",
+            err
+        );
+
+        let fd = open_archive_fd(&["7z", "comics_test_encrypted.cb7"]);
+        let err = SevenZipArchive::new(fd)
+            .with_password("wrong password")
+            .open_lazy()
+            .unwrap_err();
+        assert!(
+            matches!(err, SevenZipError::WrongPassword),
+            "opening an encrypted archive with the wrong password should report WrongPassword, got {:?}",
+            err
+        );
+
+        let fd = open_archive_fd(&["7z", "comics_test_encrypted.cb7"]);
+        let archive = SevenZipArchive::new(fd)
+            .with_password(PASSWORD)
+            .open_lazy()
+            .unwrap();
+
+        let entries = archive.list_entries().unwrap();
+        assert!(!entries.is_empty(), "the correct password should unlock the archive's entries");
+
+        let file_pos = entries
+            .iter()
+            .find(|e| !e.is_dir)
+            .expect("fixture should contain at least one file")
+            .pos as u32;
+
+        archive
+            .read_entry(file_pos)
+            .expect("the correct password should allow extracting content");
+    }
+
     #[cfg(target_family = "unix")]
     fn open_7z_fd() -> RawFd {
         open_archive_fd(&["7z", "comics_test.cb7"])
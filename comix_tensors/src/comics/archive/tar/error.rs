@@ -0,0 +1,26 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+///Errors which can occur while reading a tar/cbt archive
+#[derive(Debug)]
+pub enum TarError {
+    ///Underlying IO error, including malformed archive headers
+    Io(io::Error),
+}
+
+impl fmt::Display for TarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TarError::Io(e) => write!(f, "tar archive IO error: {}", e),
+        }
+    }
+}
+
+impl Error for TarError {}
+
+impl From<io::Error> for TarError {
+    fn from(e: io::Error) -> Self {
+        TarError::Io(e)
+    }
+}
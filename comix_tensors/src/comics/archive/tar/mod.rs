@@ -0,0 +1,403 @@
+mod error;
+
+use self::error::TarError;
+use super::{ArchiveFile, ComicContainer, ComicFilesStream};
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::os::unix::io::{FromRawFd, RawFd};
+
+use flate2::read::GzDecoder;
+use libc::dup;
+
+use tokio::prelude::*;
+
+type TarResult<T> = Result<T, TarError>;
+
+const BLOCK_SIZE: usize = 512;
+///Refuse to allocate more than this for a single entry's content. A comic page is nowhere near this
+///size, so anything larger is almost certainly a corrupted or malicious header (the raw `size`
+///header field can claim up to ~8 GiB)
+const MAX_ENTRY_SIZE: u64 = 512 * 1024 * 1024;
+///Offset and length of the `name` header field
+const NAME_FIELD: (usize, usize) = (0, 100);
+///Offset and length of the `mode` header field
+const MODE_FIELD: (usize, usize) = (100, 8);
+///Offset and length of the `size` header field
+const SIZE_FIELD: (usize, usize) = (124, 12);
+///Offset and length of the `mtime` header field
+const MTIME_FIELD: (usize, usize) = (136, 12);
+///Offset of the single byte `typeflag` header field
+const TYPE_FIELD: usize = 156;
+///Offset and length of the ustar `prefix` header field, prepended to `name` when non empty
+const PREFIX_FIELD: (usize, usize) = (345, 155);
+
+///Start point of the Tar/CBT archive logic. Supports both uncompressed and gzip compressed tar archives
+#[derive(Debug, Copy, Clone)]
+pub struct TarArchive {
+    fd: RawFd,
+    gzip: bool,
+}
+
+impl TarArchive {
+    ///[gzip] selects whether the underlying stream should first be gunzipped, as used by `.cbt` archives
+    ///distributed as gzip compressed tarballs
+    pub fn new(fd: RawFd, gzip: bool) -> Self {
+        debug!("File descriptor of the tar archive: {}, gzip: {}", fd, gzip);
+        TarArchive { fd, gzip }
+    }
+
+    ///Open archive in the [Future]
+    fn open(&self) -> impl Future<Item = TarEntries<Box<dyn Read + Send>>, Error = TarError> {
+        let fd = self.fd;
+        let gzip = self.gzip;
+
+        future::lazy(move || {
+            let fd = unsafe { dup(fd) };
+            let file = unsafe { File::from_raw_fd(fd) };
+
+            let reader: Box<dyn Read + Send> = if gzip {
+                Box::new(GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+
+            Ok(TarEntries::new(reader))
+        })
+    }
+
+    ///Stream over all files in the tar archive
+    fn stream_files(&self) -> impl Stream<Item = ArchiveFile, Error = TarError> {
+        self.open().map(stream::iter_result).flatten_stream()
+    }
+}
+
+impl ComicContainer for TarArchive {
+    fn files(&self) -> ComicFilesStream {
+        Box::new(self.stream_files().from_err())
+    }
+}
+
+///Iterator over entries of a tar stream, modeled on the tar-rs `Entries` iterator: headers are
+///read one at a time, content is read lazily and the stream position is advanced past the
+///512 byte padding before the next header
+struct TarEntries<R> {
+    reader: R,
+    pos: usize,
+    //GNU long name (typeflag 'L') or PAX path (typeflag 'x'/'g') carried over onto the next entry
+    pending_name: Option<String>,
+}
+
+impl<R: Read> TarEntries<R> {
+    fn new(reader: R) -> Self {
+        TarEntries {
+            reader,
+            pos: 0,
+            pending_name: None,
+        }
+    }
+
+    ///Read the next 512 byte header, returning `None` once the end-of-archive marker (a zeroed
+    ///block) is reached. A single `Read::read` call is allowed to return fewer bytes than the
+    ///buffer it's given for reasons other than EOF (routine for `GzDecoder`, which this struct
+    ///wraps for `.cbt.gz` archives), so reads are accumulated until the header is full; only a
+    ///zero-byte read at a fresh header boundary is treated as the true end of the archive, while a
+    ///short read that hits EOF mid header is a genuine error rather than a silently truncated block
+    fn read_header(&mut self) -> io::Result<Option<[u8; BLOCK_SIZE]>> {
+        let mut header = [0u8; BLOCK_SIZE];
+        let mut filled = 0;
+
+        while filled < BLOCK_SIZE {
+            let read = self.reader.read(&mut header[filled..])?;
+
+            if read == 0 {
+                break;
+            }
+
+            filled += read;
+        }
+
+        if filled == 0 {
+            return Ok(None);
+        }
+
+        if filled < BLOCK_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "tar archive ends with a truncated header",
+            ));
+        }
+
+        if header.iter().all(|&b| b == 0) {
+            return Ok(None);
+        }
+
+        Ok(Some(header))
+    }
+
+    ///Read [size] bytes of entry content. [size] comes straight from an (attacker-controlled)
+    ///header field, so it's rejected outright past [MAX_ENTRY_SIZE] rather than handed to `vec!`,
+    ///which would zero-fill allocate it unconditionally and abort the process on failure
+    fn read_content(&mut self, size: u64) -> io::Result<Vec<u8>> {
+        if size > MAX_ENTRY_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "tar entry size {} exceeds the {} byte limit",
+                    size, MAX_ENTRY_SIZE
+                ),
+            ));
+        }
+
+        let mut content = Vec::with_capacity(size as usize);
+        self.reader.by_ref().take(size).read_to_end(&mut content)?;
+
+        if content.len() as u64 != size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "tar entry content is shorter than its header's declared size",
+            ));
+        }
+
+        Ok(content)
+    }
+
+    ///Skip the zero padding up to the next 512 byte header boundary
+    fn skip_padding(&mut self, size: u64) -> io::Result<()> {
+        let padding = (BLOCK_SIZE as u64 - (size % BLOCK_SIZE as u64)) % BLOCK_SIZE as u64;
+        io::copy(&mut self.reader.by_ref().take(padding), &mut io::sink()).map(|_| ())
+    }
+
+    fn skip_content(&mut self, size: u64) -> io::Result<()> {
+        io::copy(&mut self.reader.by_ref().take(size), &mut io::sink())?;
+        self.skip_padding(size)
+    }
+}
+
+impl<R: Read> Iterator for TarEntries<R> {
+    type Item = TarResult<ArchiveFile>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        macro_rules! try_io {
+            ($e:expr) => {
+                match $e {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e.into())),
+                }
+            };
+        }
+
+        loop {
+            let header = match try_io!(self.read_header()) {
+                Some(header) => header,
+                None => return None,
+            };
+
+            let size = try_io!(parse_octal(field(&header, SIZE_FIELD)));
+            let type_flag = header[TYPE_FIELD];
+
+            match type_flag {
+                //GNU long name: content of this entry is the real name of the one that follows
+                b'L' => {
+                    let content = try_io!(self.read_content(size));
+                    try_io!(self.skip_padding(size));
+                    self.pending_name = Some(cstr_field(&content));
+                }
+                //PAX extended header: look for a "path" record among its `<len> key=value\n` entries
+                b'x' | b'g' => {
+                    let content = try_io!(self.read_content(size));
+                    try_io!(self.skip_padding(size));
+
+                    if let Some(path) = parse_pax_path(&content) {
+                        self.pending_name = Some(path);
+                    }
+                }
+                //directory: no content to read
+                b'5' => {
+                    let name = self.pending_name.take().unwrap_or_else(|| parse_name(&header));
+                    let mode = try_io!(parse_octal(field(&header, MODE_FIELD)));
+                    let mtime = try_io!(parse_octal(field(&header, MTIME_FIELD)));
+                    let pos = self.pos;
+                    self.pos += 1;
+
+                    return Some(Ok(ArchiveFile {
+                        pos,
+                        name,
+                        is_dir: true,
+                        content: None,
+                        size: 0,
+                        mtime: Some(mtime),
+                        attributes: Some(mode as u32),
+                    }));
+                }
+                //regular file (old and ustar format both use '0', and a NUL byte in legacy archives)
+                b'0' | 0 => {
+                    let name = self.pending_name.take().unwrap_or_else(|| parse_name(&header));
+                    let mode = try_io!(parse_octal(field(&header, MODE_FIELD)));
+                    let mtime = try_io!(parse_octal(field(&header, MTIME_FIELD)));
+                    let content = try_io!(self.read_content(size));
+                    try_io!(self.skip_padding(size));
+
+                    let pos = self.pos;
+                    self.pos += 1;
+
+                    return Some(Ok(ArchiveFile {
+                        pos,
+                        name,
+                        is_dir: false,
+                        content: Some(content),
+                        size,
+                        mtime: Some(mtime),
+                        attributes: Some(mode as u32),
+                    }));
+                }
+                //symlinks, hardlinks, devices, etc. aren't comic pages, skip them entirely
+                _ => {
+                    self.pending_name = None;
+                    try_io!(self.skip_content(size));
+                }
+            }
+        }
+    }
+}
+
+fn field<'a>(header: &'a [u8; BLOCK_SIZE], (offset, len): (usize, usize)) -> &'a [u8] {
+    &header[offset..offset + len]
+}
+
+///Parse a NUL-padded octal numeric header field, as used for `size`
+fn parse_octal(field: &[u8]) -> io::Result<u64> {
+    let text = std::str::from_utf8(field)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "non UTF-8 tar header field"))?
+        .trim_matches(|c: char| c == '\0' || c.is_whitespace());
+
+    if text.is_empty() {
+        return Ok(0);
+    }
+
+    u64::from_str_radix(text, 8)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid octal tar header field"))
+}
+
+///Decode the ustar `name`/`prefix` fields of a header into a single path
+fn parse_name(header: &[u8; BLOCK_SIZE]) -> String {
+    let prefix = cstr_field(field(header, PREFIX_FIELD));
+    let name = cstr_field(field(header, NAME_FIELD));
+
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+///Decode a NUL-terminated (or full-width) header field as a lossy UTF-8 string
+fn cstr_field(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+///Extract the `path` record from a PAX extended header block
+fn parse_pax_path(content: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(content);
+
+    const PATH_PREFIX: &str = "path=";
+
+    text.split('\n').find_map(|record| {
+        let value = record.splitn(2, ' ').nth(1)?;
+
+        if value.starts_with(PATH_PREFIX) {
+            Some(value[PATH_PREFIX.len()..].to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comics::archive::tests::open_archive_fd;
+    use crate::comics::magic::{resolve_file_magic_type, MagicType};
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_stream_cbt_archive() {
+        let fd = open_archive_fd(&["tar", "comics_test.cbt"]);
+
+        let mut file_count = 0u32;
+
+        TarArchive::new(fd, false)
+            .stream_files()
+            .wait()
+            .for_each(|file| {
+                let file = file.unwrap();
+
+                assert_eq!(
+                    file.content.is_none(),
+                    file.is_dir,
+                    "If it's a file it should contain content. Otherwise it should be empty"
+                );
+
+                file_count += 1;
+            });
+
+        assert_eq!(
+            file_count, 11,
+            "Wrong number of tar archive files. Count {}",
+            file_count
+        );
+    }
+
+    #[test]
+    #[cfg(target_family = "unix")]
+    fn test_guess_cbt_gz_magic_type() {
+        let fd = open_archive_fd(&["tar", "comics_test.cbt.gz"]);
+        let mut file = unsafe { File::from_raw_fd(fd) };
+
+        let res = resolve_file_magic_type(&mut file).unwrap();
+        assert_eq!(res, MagicType::TARGZ);
+    }
+
+    ///Reader that only ever hands back a single byte per `read` call, standing in for a
+    ///`GzDecoder` returning a short read in the middle of a header
+    struct OneByteAtATime<R>(R);
+
+    impl<R: Read> Read for OneByteAtATime<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            self.0.read(&mut buf[..1])
+        }
+    }
+
+    #[test]
+    fn test_read_header_survives_short_reads() {
+        let mut header = [0u8; BLOCK_SIZE];
+        header[TYPE_FIELD] = b'0';
+        header[NAME_FIELD.0..NAME_FIELD.0 + 4].copy_from_slice(b"foo\0");
+
+        let mut entries = TarEntries::new(OneByteAtATime(&header[..]));
+
+        let parsed = entries
+            .read_header()
+            .unwrap()
+            .expect("a full header should be assembled despite 1-byte-at-a-time reads");
+
+        assert_eq!(&parsed[..], &header[..]);
+    }
+
+    #[test]
+    fn test_read_header_rejects_truncated_header() {
+        let header = [0u8; BLOCK_SIZE / 2];
+
+        let mut entries = TarEntries::new(OneByteAtATime(&header[..]));
+
+        entries
+            .read_header()
+            .expect_err("a header that hits EOF before filling 512 bytes is a truncated archive, not a clean end");
+    }
+}
@@ -0,0 +1,17 @@
+use std::fs::File;
+use std::os::unix::io::{IntoRawFd, RawFd};
+use std::path::PathBuf;
+
+///Open a test resource file located at `test_resources/<path components>` and return its raw FD
+pub(crate) fn open_archive_fd(path: &[&str]) -> RawFd {
+    let mut full_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    full_path.push("test_resources");
+
+    for part in path {
+        full_path.push(part);
+    }
+
+    File::open(&full_path)
+        .unwrap_or_else(|e| panic!("Can't open test archive '{:?}': {}", full_path, e))
+        .into_raw_fd()
+}
@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+
+///Magic types of the archives supported as comic book containers
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum MagicType {
+    ///7z archive (`.cb7`)
+    SZ,
+    ///Uncompressed tar archive (`.cbt`)
+    TAR,
+    ///Gzip compressed tar archive (`.cbt`)
+    TARGZ,
+    ///Unknown magic bytes
+    Unknown,
+}
+
+const SZ_MAGIC: [u8; 6] = [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const TAR_MAGIC_OFFSET: u64 = 257;
+const TAR_MAGIC: [u8; 5] = *b"ustar";
+
+///Resolve [MagicType] of the provided [File] by reading its leading bytes.
+///The file position is restored before returning.
+pub fn resolve_file_magic_type(file: &mut File) -> io::Result<MagicType> {
+    let start_pos = file.seek(SeekFrom::Current(0))?;
+
+    let magic_type = (|| {
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut header = [0u8; 6];
+        let read = file.read(&mut header)?;
+
+        if read >= SZ_MAGIC.len() && header[..SZ_MAGIC.len()] == SZ_MAGIC {
+            return Ok(MagicType::SZ);
+        }
+
+        if read >= GZIP_MAGIC.len() && header[..GZIP_MAGIC.len()] == GZIP_MAGIC {
+            return Ok(MagicType::TARGZ);
+        }
+
+        file.seek(SeekFrom::Start(TAR_MAGIC_OFFSET))?;
+
+        let mut tar_magic = [0u8; 5];
+
+        if file.read(&mut tar_magic)? == tar_magic.len() && tar_magic == TAR_MAGIC {
+            return Ok(MagicType::TAR);
+        }
+
+        Ok(MagicType::Unknown)
+    })();
+
+    file.seek(SeekFrom::Start(start_pos))?;
+
+    magic_type
+}